@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use analysis::tsv_reader::{self, merge_periods};
+use analysis::viewingperiod::ViewingPeriod;
+use analysis::writer::{write_periods, Format as WriteFormat};
+
+/// Filter, merge, and convert viewing-period CSV/TSV/NDJSON exports.
+#[derive(Parser)]
+#[command(name = "periods")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// After processing, print per-file and overall counts by status;
+    /// `merge` reports its combined output as a single entry, not per file
+    #[arg(long, global = true)]
+    summary: bool
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Filter one or more files to periods overlapping a `[--start, --end)`
+    /// window: a period is kept if its query_time is before `--end` and its
+    /// end time is not before `--start`
+    Range {
+        /// Input files; `.csv`/`.tsv`, optionally `.gz`-compressed, or `-` for stdin
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        #[arg(long, value_parser = parse_rfc3339)]
+        start: Option<DateTime<Utc>>,
+
+        #[arg(long, value_parser = parse_rfc3339)]
+        end: Option<DateTime<Utc>>,
+
+        #[arg(long, value_enum, default_value = "csv")]
+        format: OutputFormat,
+
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Field delimiter for `-` (stdin); ignored for files, whose
+        /// extension decides it
+        #[arg(long, default_value = ",")]
+        sep: char
+    },
+    /// Interleave multiple files in query_time order, optionally filtered
+    /// to a `[--start, --end)` window
+    Merge {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        #[arg(long, value_parser = parse_rfc3339)]
+        start: Option<DateTime<Utc>>,
+
+        #[arg(long, value_parser = parse_rfc3339)]
+        end: Option<DateTime<Utc>>,
+
+        #[arg(long, value_enum, default_value = "csv")]
+        format: OutputFormat,
+
+        #[arg(long)]
+        out: Option<PathBuf>
+    },
+    /// Change delimiter/format: CSV, TSV, or newline-delimited JSON
+    Convert {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+
+        #[arg(long, value_enum)]
+        format: OutputFormat,
+
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Field delimiter for `-` (stdin); ignored for files, whose
+        /// extension decides it
+        #[arg(long, default_value = ",")]
+        sep: char
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum OutputFormat {
+    Csv,
+    Tsv,
+    Ndjson
+}
+
+impl From<OutputFormat> for WriteFormat {
+    fn from(value: OutputFormat) -> Self {
+        match value {
+            OutputFormat::Csv => WriteFormat::Csv,
+            OutputFormat::Tsv => WriteFormat::Tsv,
+            OutputFormat::Ndjson => WriteFormat::Ndjson
+        }
+    }
+}
+
+fn parse_rfc3339(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|err| err.to_string())
+}
+
+/// Reads one input path, printing any per-row `ReadError`s rather than
+/// aborting, and returns whatever periods did parse. `sep` only matters
+/// for `-` (stdin); a file's own extension decides its delimiter.
+fn read_path(path: &Path, sep: char) -> Vec<ViewingPeriod> {
+    let path_str = path.to_string_lossy();
+    match tsv_reader::read_any(&path_str, sep) {
+        Ok((periods, errors)) => {
+            for err in errors {
+                eprintln!("{}: {}", path.display(), err);
+            }
+            periods
+        }
+        Err(err) => {
+            eprintln!("{}: {}", path.display(), err);
+            Vec::new()
+        }
+    }
+}
+
+fn in_range(period: &ViewingPeriod, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> bool {
+    let before_start = start.is_some_and(|s| period.end_time() < s);
+    let at_or_after_end = end.is_some_and(|e| period.query_time() >= e);
+    !before_start && !at_or_after_end
+}
+
+/// Per-file counts by `Status`, reported alongside the combined totals when
+/// `--summary` is passed.
+struct FileSummary {
+    path: PathBuf,
+    by_status: BTreeMap<String, usize>,
+    valid: usize,
+    min_query_time: Option<DateTime<Utc>>,
+    max_query_time: Option<DateTime<Utc>>
+}
+
+impl FileSummary {
+    fn new(path: PathBuf) -> Self {
+        FileSummary {
+            path,
+            by_status: BTreeMap::new(),
+            valid: 0,
+            min_query_time: None,
+            max_query_time: None
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.by_status.values().sum()
+    }
+
+    fn record(&mut self, period: &ViewingPeriod) {
+        *self.by_status.entry(period.status().to_string()).or_insert(0) += 1;
+        if period.valid() {
+            self.valid += 1;
+        }
+        let query_time = period.query_time();
+        self.min_query_time = Some(self.min_query_time.map_or(query_time, |t| t.min(query_time)));
+        self.max_query_time = Some(self.max_query_time.map_or(query_time, |t| t.max(query_time)));
+    }
+}
+
+fn print_summary(summaries: &[FileSummary]) {
+    let mut overall = FileSummary::new(PathBuf::from("TOTAL"));
+
+    for summary in summaries {
+        eprintln!("{}:", summary.path.display());
+        for (status, count) in &summary.by_status {
+            eprintln!("  {}: {}", status, count);
+            *overall.by_status.entry(status.clone()).or_insert(0) += count;
+        }
+        eprintln!("  total: {}, valid: {}", summary.total(), summary.valid);
+
+        overall.valid += summary.valid;
+        if let Some(min) = summary.min_query_time {
+            overall.min_query_time = Some(overall.min_query_time.map_or(min, |t| t.min(min)));
+        }
+        if let Some(max) = summary.max_query_time {
+            overall.max_query_time = Some(overall.max_query_time.map_or(max, |t| t.max(max)));
+        }
+    }
+
+    eprintln!("overall:");
+    for (status, count) in &overall.by_status {
+        eprintln!("  {}: {}", status, count);
+    }
+    eprintln!("  total: {}, valid: {}", overall.total(), overall.valid);
+    if let (Some(min), Some(max)) = (overall.min_query_time, overall.max_query_time) {
+        eprintln!("  query_time range: {} .. {}", min.to_rfc3339(), max.to_rfc3339());
+    }
+}
+
+fn open_sink(out: &Option<PathBuf>) -> io::Result<Box<dyn Write>> {
+    match out {
+        Some(path) => Ok(Box::new(File::create(path)?)),
+        None => Ok(Box::new(io::stdout().lock()))
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let (periods, summaries, format, out): (Vec<ViewingPeriod>, Vec<FileSummary>, OutputFormat, Option<PathBuf>) = match cli.command {
+        Command::Range { paths, start, end, format, out, sep } => {
+            let mut periods = Vec::new();
+            let mut summaries = Vec::new();
+
+            for path in paths {
+                let mut summary = FileSummary::new(path.clone());
+                for period in read_path(&path, sep).into_iter().filter(|p| in_range(p, start, end)) {
+                    summary.record(&period);
+                    periods.push(period);
+                }
+                summaries.push(summary);
+            }
+
+            (periods, summaries, format, out)
+        }
+        Command::Merge { paths, start, end, format, out } => {
+            let mut summary = FileSummary::new(PathBuf::from("MERGED"));
+            let periods: Vec<_> = merge_periods(&paths, start, end)
+                .inspect(|period| summary.record(period))
+                .collect();
+            let summaries = vec![summary];
+            (periods, summaries, format, out)
+        }
+        Command::Convert { paths, format, out, sep } => {
+            let mut periods = Vec::new();
+            let mut summaries = Vec::new();
+
+            for path in paths {
+                let mut summary = FileSummary::new(path.clone());
+                for period in read_path(&path, sep) {
+                    summary.record(&period);
+                    periods.push(period);
+                }
+                summaries.push(summary);
+            }
+
+            (periods, summaries, format, out)
+        }
+    };
+
+    let sink = open_sink(&out).expect("failed to open output");
+    write_periods(&periods, sink, format.into()).expect("failed to write periods");
+
+    if cli.summary {
+        print_summary(&summaries);
+    }
+}
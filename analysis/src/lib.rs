@@ -0,0 +1,6 @@
+#![allow(clippy::needless_return)]
+
+pub mod parallel;
+pub mod tsv_reader;
+pub mod viewingperiod;
+pub mod writer;
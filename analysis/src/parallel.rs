@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::tsv_reader;
+use crate::viewingperiod::ViewingPeriod;
+
+/// Counts accumulated while reading one or more period files: the number
+/// of periods by `Status`, how many were valid vs invalid, the total
+/// viewing duration, and the earliest/latest `end_time()` seen.
+#[derive(Clone)]
+pub struct Counts {
+    pub by_status: BTreeMap<String, usize>,
+    pub valid: usize,
+    pub invalid: usize,
+    pub total_duration: Duration,
+    pub earliest_end: Option<DateTime<Utc>>,
+    pub latest_end: Option<DateTime<Utc>>
+}
+
+impl Default for Counts {
+    fn default() -> Self {
+        Counts {
+            by_status: BTreeMap::new(),
+            valid: 0,
+            invalid: 0,
+            total_duration: Duration::zero(),
+            earliest_end: None,
+            latest_end: None
+        }
+    }
+}
+
+impl Counts {
+    fn record(&mut self, period: &ViewingPeriod) {
+        *self.by_status.entry(period.status().to_string()).or_insert(0) += 1;
+        if period.valid() {
+            self.valid += 1;
+        } else {
+            self.invalid += 1;
+        }
+        self.total_duration += period.duration();
+
+        let end = period.end_time();
+        self.earliest_end = Some(self.earliest_end.map_or(end, |t| t.min(end)));
+        self.latest_end = Some(self.latest_end.map_or(end, |t| t.max(end)));
+    }
+
+    fn merge(&mut self, other: &Counts) {
+        for (status, count) in &other.by_status {
+            *self.by_status.entry(status.clone()).or_insert(0) += count;
+        }
+        self.valid += other.valid;
+        self.invalid += other.invalid;
+        self.total_duration += other.total_duration;
+
+        if let Some(end) = other.earliest_end {
+            self.earliest_end = Some(self.earliest_end.map_or(end, |t| t.min(end)));
+        }
+        if let Some(end) = other.latest_end {
+            self.latest_end = Some(self.latest_end.map_or(end, |t| t.max(end)));
+        }
+    }
+}
+
+/// Per-file and overall `Counts` produced by [`process_parallel`].
+#[derive(Default)]
+pub struct Summary {
+    pub overall: Counts,
+    pub per_file: BTreeMap<PathBuf, Counts>
+}
+
+/// Reads `paths` across a pool of up to `num_threads` worker threads (each
+/// file is parsed start-to-finish on whichever worker picks it up) and
+/// funnels the results back over an `mpsc` channel to this thread, which
+/// merges them into one `query_time`-sorted `Vec` plus an aggregated
+/// [`Summary`]. Read errors are logged to stderr rather than aborting the
+/// batch, matching [`tsv_reader::read_any`]'s row-level error handling.
+pub fn process_parallel(paths: &[PathBuf], num_threads: usize) -> (Vec<ViewingPeriod>, Summary) {
+    let queue = Arc::new(Mutex::new(paths.to_vec()));
+    let (tx, rx) = mpsc::channel();
+    let worker_count = num_threads.max(1).min(paths.len().max(1));
+
+    let workers: Vec<_> = (0..worker_count)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let tx = tx.clone();
+
+            thread::spawn(move || {
+                loop {
+                    let path = queue.lock()
+                        .expect("path queue mutex poisoned")
+                        .pop();
+
+                    let Some(path) = path else { break };
+
+                    let path_str = path.to_string_lossy().into_owned();
+                    let result = tsv_reader::read_any(&path_str, ',');
+                    tx.send((path, result)).expect("collector channel closed early");
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let mut periods = Vec::new();
+    let mut summary = Summary::default();
+
+    for (path, result) in rx {
+        let mut counts = Counts::default();
+
+        match result {
+            Ok((file_periods, errors)) => {
+                for err in &errors {
+                    eprintln!("{}: {}", path.display(), err);
+                }
+                for period in file_periods {
+                    counts.record(&period);
+                    periods.push(period);
+                }
+            }
+            Err(err) => eprintln!("{}: {}", path.display(), err)
+        }
+
+        summary.overall.merge(&counts);
+        summary.per_file.insert(path, counts);
+    }
+
+    for worker in workers {
+        worker.join().expect("worker thread panicked");
+    }
+
+    periods.sort_by_key(|period| period.query_time());
+
+    (periods, summary)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_process_parallel_merges_files_in_query_time_order() {
+        let dir = std::env::temp_dir().join(format!("parallel_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("expected to create temp dir");
+
+        let path_a = dir.join("a.csv");
+        let path_b = dir.join("b.csv");
+        fs::write(&path_a, "userID,startTime,durationMsec,status,valid\n\
+            1,2023-01-12 14:00:00.000,1000,MATCH,1\n")
+            .expect("expected to write temp file");
+        fs::write(&path_b, "userID,startTime,durationMsec,status,valid\n\
+            2,2023-01-12 13:00:00.000,1000,NO_MATCH,0\n")
+            .expect("expected to write temp file");
+
+        let (periods, summary) = process_parallel(&[path_a.clone(), path_b.clone()], 4);
+
+        fs::remove_dir_all(&dir).expect("expected to clean up temp dir");
+
+        assert_eq!(periods.len(), 2);
+        assert!(periods[0].query_time() < periods[1].query_time(), "expected periods sorted by query_time");
+        assert_eq!(periods[0].user_id, "2");
+
+        assert_eq!(summary.overall.valid, 1);
+        assert_eq!(summary.overall.invalid, 1);
+        assert_eq!(summary.per_file.get(&path_a).unwrap().valid, 1);
+        assert_eq!(summary.per_file.get(&path_b).unwrap().invalid, 1);
+    }
+}
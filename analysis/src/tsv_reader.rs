@@ -1,117 +1,309 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::str::FromStr;
-use std::{path::Path, io::BufReader};
-use std::io::{Read, BufRead};
+use std::{path::Path, path::PathBuf, io::BufReader};
+use std::io::BufRead;
 use std::fs;
 
-use chrono::{DateTime, TimeZone, Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use flate2::read::GzDecoder;
 
 use crate::viewingperiod::{ViewingPeriod, Status};
 
 /// Holds the names of the columns in a TSV or CSV file
-type Header<'a> = Vec<&'a str>;
+type Header = Vec<String>;
+
+/// Everything that can go wrong while reading a periods file. Errors that
+/// originate from a single row (a bad timestamp, a short row, ...) carry the
+/// 1-based line number they came from so a caller can report or skip them.
+#[derive(Debug)]
+pub enum ReadError {
+    UnsupportedExtension,
+    Io(String),
+    MissingHeader,
+    BadTimestamp { line: usize, field: &'static str, value: String },
+    BadStatus { line: usize, value: String },
+    BadFloat { line: usize, field: &'static str, value: String },
+    ShortRow { line: usize, expected: usize, got: usize }
+}
+
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedExtension => write!(f, "unsupported file extension"),
+            Self::Io(message) => write!(f, "i/o error: {}", message),
+            Self::MissingHeader => write!(f, "expected table to have at least a header row"),
+            Self::BadTimestamp { line, field, value } =>
+                write!(f, "line {}: could not parse '{}' as a timestamp for field '{}'", line, value, field),
+            Self::BadStatus { line, value } =>
+                write!(f, "line {}: '{}' is not a recognised status", line, value),
+            Self::BadFloat { line, field, value } =>
+                write!(f, "line {}: could not parse '{}' as a number for field '{}'", line, value, field),
+            Self::ShortRow { line, expected, got } =>
+                write!(f, "line {}: expected {} fields, got {}", line, expected, got)
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
 
+/// Strips a trailing `.gz` before inspecting the extension, so a
+/// gzip-compressed `foo.csv.gz` is treated the same as `foo.csv`.
 fn separator(path: impl AsRef<Path>) -> Option<char> {
-    match path.as_ref().extension() {
+    let path = path.as_ref();
+    let path = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("gz") => path.with_extension(""),
+        _ => path.to_path_buf()
+    };
+
+    match path.extension().and_then(|ext| ext.to_str()) {
         None => None,
-        Some(os_str) => match os_str.to_str() {
-            None => None,
-            Some("csv") => Some(','),
-            Some("tsv") => Some('\t'),
-            Some(_) => None
-        }
+        Some("csv") => Some(','),
+        Some("tsv") => Some('\t'),
+        Some(_) => None
     }
 }
 
-fn read(path: impl AsRef<Path>) -> Vec<ViewingPeriod> {
+fn is_gz(path: impl AsRef<Path>) -> bool {
+    path.as_ref().extension().and_then(|ext| ext.to_str()) == Some("gz")
+}
+
+fn read(path: impl AsRef<Path>) -> Result<(Vec<ViewingPeriod>, Vec<ReadError>), ReadError> {
     let sep = separator(&path)
-        .expect("unsupported file extension");
+        .ok_or(ReadError::UnsupportedExtension)?;
 
     let file = fs::File::open(&path)
-        .expect("failed to open file");
+        .map_err(|err| ReadError::Io(err.to_string()))?;
+
+    if is_gz(&path) {
+        return read_periods(GzDecoder::new(file), sep);
+    }
+
+    read_periods(file, sep)
+}
+
+/// Reads periods from `path`, which may be a `.csv`/`.tsv` file (optionally
+/// `.gz`-compressed), or the literal `-` to read `sep`-delimited data from
+/// stdin. Stdin carries no extension to infer a delimiter from, so `sep`
+/// must be supplied explicitly in that case.
+///
+/// The outer `Result` only reports failures that prevent reading at all
+/// (bad extension, missing header, ...); individual bad rows are collected
+/// into the returned `Vec<ReadError>` alongside the rows that did parse.
+pub fn read_any(path: &str, sep: char) -> Result<(Vec<ViewingPeriod>, Vec<ReadError>), ReadError> {
+    if path == "-" {
+        return read_periods(std::io::stdin().lock(), sep);
+    }
+
+    read(path)
+}
+
+fn read_periods(source: impl std::io::Read, sep: char) -> Result<(Vec<ViewingPeriod>, Vec<ReadError>), ReadError> {
+    let mut periods = Vec::new();
+    let mut errors = Vec::new();
+
+    for result in period_iter(source, sep)? {
+        match result {
+            Ok(period) => periods.push(period),
+            Err(err) => errors.push(err)
+        }
+    }
 
-    return read_periods(file, sep);
+    Ok((periods, errors))
 }
 
-fn read_periods(source: impl std::io::Read, sep: char) -> Vec<ViewingPeriod> {
+/// Lazily yields the periods in `source`, reading and parsing one line at a
+/// time rather than buffering the whole file. A row that fails to parse
+/// yields an `Err` for that row rather than aborting the rest of the file;
+/// only a missing header or an I/O failure reading it is fatal.
+fn period_iter(source: impl std::io::Read, sep: char) -> Result<impl Iterator<Item = Result<ViewingPeriod, ReadError>>, ReadError> {
     let reader = BufReader::new(source);
     let mut lines_iter = reader.lines();
     let header_line = lines_iter.next()
-        .expect("expected table to have at least header")
-        .expect("failed to read header from file");
-    let header: Header = header_line.split(sep).collect();
-
-    return lines_iter
-        .filter_map(|line|
-            line.map_err(|err| println!("failed to read period line: {}", err)).ok())
-        .map(|line| line_to_period(&line, &header, sep))
+        .ok_or(ReadError::MissingHeader)?
+        .map_err(|err| ReadError::Io(err.to_string()))?;
+    let header: Header = header_line.split(sep).map(|s| s.to_string()).collect();
+
+    Ok(lines_iter
+        .enumerate()
+        .map(move |(idx, line)| {
+            let line_no = idx + 2;
+            let line = line.map_err(|err| ReadError::Io(format!("line {}: {}", line_no, err)))?;
+            line_to_period(line_no, &line, &header, sep)
+        }))
+}
+
+/// Opens `path` (transparently handling `.gz` compression) and returns a
+/// lazy, boxed iterator over its periods so callers can merge many files
+/// without loading any of them fully into memory.
+fn open_periods(path: impl AsRef<Path>) -> Result<Box<dyn Iterator<Item = Result<ViewingPeriod, ReadError>>>, ReadError> {
+    let sep = separator(&path)
+        .ok_or(ReadError::UnsupportedExtension)?;
+
+    let file = fs::File::open(&path)
+        .map_err(|err| ReadError::Io(err.to_string()))?;
+
+    let iter: Box<dyn Iterator<Item = Result<ViewingPeriod, ReadError>>> = if is_gz(&path) {
+        Box::new(period_iter(GzDecoder::new(file), sep)?)
+    } else {
+        Box::new(period_iter(file, sep)?)
+    };
+
+    Ok(iter)
+}
+
+/// Pulls the next successfully-parsed period from `iter`, logging and
+/// skipping over any bad rows along the way.
+fn next_ok(iter: &mut Box<dyn Iterator<Item = Result<ViewingPeriod, ReadError>>>) -> Option<ViewingPeriod> {
+    for result in iter.by_ref() {
+        match result {
+            Ok(period) => return Some(period),
+            Err(err) => eprintln!("skipping unreadable row: {}", err)
+        }
+    }
+    None
+}
+
+/// Lazily merges `paths` into a single stream of periods globally sorted by
+/// `query_time`, optionally restricted to the half-open range
+/// `[start, end)`: a period is kept if its `query_time` is before `end` and
+/// its `end_time()` is not before `start`, so a period that began before
+/// `start` but is still running at `start` is kept. Each input file is assumed to already be sorted ascending
+/// by `query_time`; the merge only ever holds one buffered period per file,
+/// so memory use stays flat regardless of how much data the files contain.
+/// Once the smallest buffered `query_time` reaches `end`, every other
+/// buffered or not-yet-read period is at least that large too, so the
+/// merge stops there instead of draining the rest of every file.
+pub fn merge_periods(
+    paths: &[PathBuf],
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>
+) -> impl Iterator<Item = ViewingPeriod> {
+    let iters: Vec<Box<dyn Iterator<Item = Result<ViewingPeriod, ReadError>>>> = paths.iter()
+        .filter_map(|path| match open_periods(path) {
+            Ok(iter) => Some(iter),
+            Err(err) => {
+                eprintln!("skipping {}: {}", path.display(), err);
+                None
+            }
+        })
         .collect();
+
+    merge_iters(iters, start, end)
 }
 
-fn set_status(period: &mut ViewingPeriod, value: &str) {
-    match Status::from_str(value) {
-        Ok(status) => period.status = status,
-        Err(err) => println!("failed to parse status '{}'", value)
+/// The merge logic behind [`merge_periods`], operating on already-opened
+/// period iterators rather than file paths so it can be exercised directly.
+fn merge_iters(
+    mut iters: Vec<Box<dyn Iterator<Item = Result<ViewingPeriod, ReadError>>>>,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>
+) -> impl Iterator<Item = ViewingPeriod> {
+    let mut buffered: Vec<Option<ViewingPeriod>> = iters.iter().map(|_| None).collect();
+    let mut heap: BinaryHeap<Reverse<(DateTime<Utc>, usize)>> = BinaryHeap::new();
+
+    for (idx, iter) in iters.iter_mut().enumerate() {
+        if let Some(period) = next_ok(iter) {
+            heap.push(Reverse((period.query_time, idx)));
+            buffered[idx] = Some(period);
+        }
     }
+
+    std::iter::from_fn(move || {
+        loop {
+            let Reverse((query_time, idx)) = heap.pop()?;
+
+            if end.is_some_and(|e| query_time >= e) {
+                // The heap invariant means every other buffered or
+                // not-yet-read period is >= query_time, hence >= end too;
+                // stop now rather than scanning the rest of every file.
+                heap.clear();
+                return None;
+            }
+
+            let period = buffered[idx].take()
+                .expect("heap entry without a buffered period");
+
+            if let Some(next) = next_ok(&mut iters[idx]) {
+                heap.push(Reverse((next.query_time, idx)));
+                buffered[idx] = Some(next);
+            }
+
+            if start.is_some_and(|s| period.end_time() < s) {
+                continue;
+            }
+
+            return Some(period);
+        }
+    })
 }
 
-fn parse_datetime_str(value: &str) -> DateTime<chrono::Utc> {
-    return chrono::Utc.datetime_from_str(value, "%F %T%.3f")
-        .expect("failed to parse datetime");
+fn set_status(line: usize, period: &mut ViewingPeriod, value: &str) -> Result<(), ReadError> {
+    Status::from_str(value)
+        .map(|status| period.status = status)
+        .map_err(|_| ReadError::BadStatus { line, value: value.to_string() })
 }
 
-fn parse_timestamp(value: &str) -> DateTime<chrono::Utc> {
+fn parse_datetime_str(line: usize, field: &'static str, value: &str) -> Result<DateTime<chrono::Utc>, ReadError> {
+    chrono::NaiveDateTime::parse_from_str(value, "%F %T%.3f")
+        .map(|naive| naive.and_utc())
+        .map_err(|_| ReadError::BadTimestamp { line, field, value: value.to_string() })
+}
+
+fn parse_timestamp(line: usize, field: &'static str, value: &str) -> Result<DateTime<chrono::Utc>, ReadError> {
     let millis = value.parse::<i64>()
-        .expect("could not parse timestamp as integer");
-    let naive = chrono::NaiveDateTime::from_timestamp_millis(millis)
-        .expect("could not convert timestamp to datetime");
-    return chrono::Utc.from_utc_datetime(&naive);
+        .map_err(|_| ReadError::BadTimestamp { line, field, value: value.to_string() })?;
+    DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| ReadError::BadTimestamp { line, field, value: value.to_string() })
 }
 
-fn duration_from_millis(value: &str) -> Duration {
+fn duration_from_millis(line: usize, field: &'static str, value: &str) -> Result<Duration, ReadError> {
     let int_value = value.parse::<i64>()
-        .expect("failed to parse millis from duration");
-    return Duration::milliseconds(int_value);
+        .map_err(|_| ReadError::BadFloat { line, field, value: value.to_string() })?;
+    Ok(Duration::milliseconds(int_value))
 }
 
-fn duration_from_seconds(value: &str) -> Duration {
+fn duration_from_seconds(line: usize, field: &'static str, value: &str) -> Result<Duration, ReadError> {
     let float_value = value.parse::<f64>()
-        .expect("failed to parse seconds from duration");
+        .map_err(|_| ReadError::BadFloat { line, field, value: value.to_string() })?;
     let milliseconds = (float_value * 1000.0).floor() as i64;
-    return Duration::milliseconds(milliseconds);
+    Ok(Duration::milliseconds(milliseconds))
 }
 
-fn line_to_period(line: &str, header: &Header, separator: char) -> ViewingPeriod {
+fn line_to_period(line_no: usize, line: &str, header: &Header, separator: char) -> Result<ViewingPeriod, ReadError> {
     let removable_chars: &[_] = &['\'', '"', ' ', ','];
     let mut vp = ViewingPeriod::default();
 
     let mut offset: Option<Duration> = None;
     let mut end_time: Option<DateTime<Utc>> = None;
 
-    for (&key, raw_value) in header.iter().zip(line.split(separator)) {
+    let fields: Vec<&str> = line.split(separator).collect();
+    if fields.len() < header.len() {
+        return Err(ReadError::ShortRow { line: line_no, expected: header.len(), got: fields.len() });
+    }
+
+    for (key, raw_value) in header.iter().zip(fields) {
 
         let value = raw_value.trim().trim_matches(removable_chars);
-        match key {
-            "status" | "Status" => set_status(&mut vp, value),
+        match key.as_str() {
+            "status" | "Status" => set_status(line_no, &mut vp, value)?,
             "userID" | "rss_id" | "DEVICE_ID" => vp.user_id = value.to_string(),
-            "timeInFile" => vp.time_in_file = parse_timestamp(value),
-            "tStartMsec" | "tStart" => vp.query_time = parse_timestamp(value),
-            "startTime" | "start_ts" | "START" => vp.query_time = parse_datetime_str(value),
-            "durationMsec" => vp.duration = duration_from_millis(value),
-            "duration" => vp.duration = duration_from_seconds(value),
+            "timeInFile" => vp.time_in_file = parse_timestamp(line_no, "timeInFile", value)?,
+            "tStartMsec" | "tStart" => vp.query_time = parse_timestamp(line_no, "tStartMsec", value)?,
+            "startTime" | "start_ts" | "START" => vp.query_time = parse_datetime_str(line_no, "startTime", value)?,
+            "durationMsec" => vp.duration = duration_from_millis(line_no, "durationMsec", value)?,
+            "duration" => vp.duration = duration_from_seconds(line_no, "duration", value)?,
             "stream_id" | "Stream_id" | "stream_name" | "name" | "STREAM_LABEL" => vp.stream_id = Some(value.to_string()),
             "module_ref" => vp.provider = Some(value.to_string()),
             "period_id" | "id" => vp.entry_id = Some(value.to_string()),
             "bitErrorRate" | "ber" => vp.ber = value.parse::<f32>()
-                .expect("failed to parse ber"),
-            "valid" => vp.valid =  match value {
-                "VALID" | "true" | "1" => true,
-                _ => false
-            },
-
-            "offset" => offset = Some(duration_from_millis(value)),
-            "offset_s" | "OFFSET" => offset = Some(duration_from_seconds(value)),
-            "endTime" | "stop_ts" | "END" => end_time = Some(parse_datetime_str(value)),
-            _ => println!("unrecognised field key {}", key)
+                .map_err(|_| ReadError::BadFloat { line: line_no, field: "ber", value: value.to_string() })?,
+            "valid" => vp.valid = matches!(value, "VALID" | "true" | "1"),
+
+            "offset" => offset = Some(duration_from_millis(line_no, "offset", value)?),
+            "offset_s" | "OFFSET" => offset = Some(duration_from_seconds(line_no, "offset_s", value)?),
+            "endTime" | "stop_ts" | "END" => end_time = Some(parse_datetime_str(line_no, "endTime", value)?),
+            _ => eprintln!("unrecognised field key {}", key)
         };
 
         if let Some(offset_val) = offset {
@@ -127,12 +319,12 @@ fn line_to_period(line: &str, header: &Header, separator: char) -> ViewingPeriod
         }
     }
 
-    return vp;
+    Ok(vp)
 }
 
 #[cfg(test)]
 mod test {
-    use chrono::{Utc, Timelike};
+    use chrono::{TimeZone, Utc, Timelike};
     use super::*;
 
     #[test]
@@ -141,11 +333,17 @@ mod test {
         let datetime = Utc.with_ymd_and_hms(2023, 1, 12, 13, 50, 0).unwrap();
 
         assert_eq!(
-            parse_timestamp(millis.to_string().as_str()),
+            parse_timestamp(2, "tStartMsec", millis.to_string().as_str()).unwrap(),
             datetime
         );
     }
 
+    #[test]
+    fn test_duration_from_millis_rejects_non_integer_as_bad_float_not_timestamp() {
+        let err = duration_from_millis(2, "durationMsec", "notanumber").unwrap_err();
+        assert!(matches!(err, ReadError::BadFloat { .. }), "expected BadFloat, got {:?}", err);
+    }
+
     #[test]
     fn test_parse_time() {
         let string = "2023-01-12 13:50:00.123";
@@ -155,11 +353,95 @@ mod test {
             .unwrap();
 
         assert_eq!(
-            parse_datetime_str(string),
+            parse_datetime_str(2, "startTime", string).unwrap(),
             datetime
         );
     }
 
+    #[test]
+    fn test_merge_periods_keeps_overlapping_period() {
+        let dir = std::env::temp_dir().join(format!("tsv_reader_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("expected to create temp dir");
+        let path = dir.join("periods.csv");
+        fs::write(&path, "userID,startTime,durationMsec,status,valid\n\
+            1,2023-01-12 12:55:00.000,600000,MATCH,1\n")
+            .expect("expected to write temp file");
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 12, 13, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2023, 1, 12, 14, 0, 0).unwrap();
+
+        let merged: Vec<_> = merge_periods(&[path], Some(start), Some(end)).collect();
+
+        fs::remove_dir_all(&dir).expect("expected to clean up temp dir");
+
+        assert_eq!(merged.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_periods_stops_pulling_past_end() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let period = |hour: u32| ViewingPeriod {
+            query_time: Utc.with_ymd_and_hms(2023, 1, 12, hour, 0, 0).unwrap(),
+            ..ViewingPeriod::default()
+        };
+
+        let pulled = Rc::new(Cell::new(0));
+        let pulled_inner = Rc::clone(&pulled);
+        let mut rows = vec![Ok(period(12)), Ok(period(13)), Ok(period(14))].into_iter();
+        let counted: Box<dyn Iterator<Item = Result<ViewingPeriod, ReadError>>> =
+            Box::new(std::iter::from_fn(move || {
+                let next = rows.next();
+                if next.is_some() {
+                    pulled_inner.set(pulled_inner.get() + 1);
+                }
+                next
+            }));
+
+        let end = Utc.with_ymd_and_hms(2023, 1, 12, 13, 0, 0).unwrap();
+        let merged: Vec<_> = merge_iters(vec![counted], None, Some(end)).collect();
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].query_time(), Utc.with_ymd_and_hms(2023, 1, 12, 12, 0, 0).unwrap());
+        assert_eq!(
+            pulled.get(), 2,
+            "expected the merge to stop once it read the period at/after `end`, without reading further rows"
+        );
+    }
+
+    #[test]
+    fn test_read_periods_reports_bad_status() {
+        let file = "userID,status\n1,NOT_A_STATUS\n";
+        let (periods, errors) = read_periods(file.as_bytes(), ',').expect("expected the file to be readable");
+
+        assert_eq!(periods.len(), 0);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], ReadError::BadStatus { .. }), "expected BadStatus, got {:?}", errors[0]);
+    }
+
+    #[test]
+    fn test_read_periods_reports_short_row() {
+        let file = "userID,status,valid\n1,MATCH\n";
+        let (periods, errors) = read_periods(file.as_bytes(), ',').expect("expected the file to be readable");
+
+        assert_eq!(periods.len(), 0);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], ReadError::ShortRow { .. }), "expected ShortRow, got {:?}", errors[0]);
+    }
+
+    #[test]
+    fn test_read_periods_rejects_missing_header() {
+        let err = read_periods("".as_bytes(), ',').unwrap_err();
+        assert!(matches!(err, ReadError::MissingHeader), "expected MissingHeader, got {:?}", err);
+    }
+
+    #[test]
+    fn test_read_any_rejects_unsupported_extension() {
+        let err = read_any("periods.txt", ',').unwrap_err();
+        assert!(matches!(err, ReadError::UnsupportedExtension), "expected UnsupportedExtension, got {:?}", err);
+    }
+
     #[test]
     fn test_parse_match() {
         let header = "id,status,period_id,stream_id,timeInFile,tStartMsec,tEndMsec,durationMsec,bitErrorRate,nMatches,userID,valid,created,client_query_id,published_ts";
@@ -181,7 +463,9 @@ mod test {
             valid: true
         };
 
-        let all_periods = read_periods(entire_file.as_bytes(), ',');
+        let (all_periods, errors) = read_periods(entire_file.as_bytes(), ',')
+            .expect("expected the file to be readable");
+        assert_eq!(errors.len(), 0);
         assert_eq!(all_periods.len(), 1);
 
         let parsed_period = all_periods.first()
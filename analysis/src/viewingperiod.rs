@@ -1,7 +1,42 @@
 use std::{str::FromStr, fmt::Debug};
 
 use chrono::{DateTime, Duration, Utc, TimeZone};
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
 
+/// Renders a `DateTime<Utc>` as RFC3339 for serde, rather than the default
+/// struct-of-fields representation `chrono::DateTime` would otherwise use.
+mod rfc3339 {
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_rfc3339_opts(SecondsFormat::Millis, true))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&raw)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Renders a `chrono::Duration` as fractional seconds for serde.
+mod duration_secs {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(value.num_milliseconds() as f64 / 1000.0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let seconds = f64::deserialize(deserializer)?;
+        Ok(Duration::milliseconds((seconds * 1000.0).round() as i64))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Status {
     Match,
     NoMatch,
@@ -47,6 +82,19 @@ impl FromStr for Status {
     }
 }
 
+impl Serialize for Status {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Status {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Status::from_str(&raw).map_err(serde::de::Error::custom)
+    }
+}
+
 impl std::convert::TryFrom<u32> for Status {
     type Error = StatusParseErr;
     fn try_from(value: u32) -> Result<Self, Self::Error> {
@@ -60,12 +108,16 @@ impl std::convert::TryFrom<u32> for Status {
     }
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct ViewingPeriod {
     pub(crate) provider: Option<String>,
     pub(crate) status: Status,
     pub(crate) user_id: String,
+    #[serde(with = "rfc3339")]
     pub(crate) query_time: DateTime<Utc>,
+    #[serde(with = "rfc3339")]
     pub(crate) time_in_file: DateTime<Utc>,
+    #[serde(with = "duration_secs")]
     pub(crate) duration: Duration,
 
     pub(crate) stream_id: Option<String>,
@@ -99,6 +151,22 @@ impl ViewingPeriod {
     pub fn offset(&self) -> Duration {
         self.query_time - self.time_in_file
     }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    pub fn query_time(&self) -> DateTime<Utc> {
+        self.query_time
+    }
+
+    pub fn valid(&self) -> bool {
+        self.valid
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
 }
 
 #[inline]
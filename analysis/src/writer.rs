@@ -0,0 +1,203 @@
+use std::io::Write;
+
+use crate::viewingperiod::ViewingPeriod;
+
+/// Output encodings `write_periods` can produce.
+pub enum Format {
+    Csv,
+    Tsv,
+    Ndjson,
+    /// Tab-delimited, header-less output modeled on bulk-ingestion prep for
+    /// a Postgres `COPY ... FROM STDIN` load: empty `provider`/`entry_id`
+    /// values and empty-or-sentinel `stream_id` values are rendered as the
+    /// `\N` NULL marker instead of an empty field.
+    PgCopy
+}
+
+#[derive(Debug)]
+pub enum WriteError {
+    Io(String),
+    Csv(String),
+    Json(String)
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(message) => write!(f, "i/o error: {}", message),
+            Self::Csv(message) => write!(f, "csv error: {}", message),
+            Self::Json(message) => write!(f, "json error: {}", message)
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+impl From<std::io::Error> for WriteError {
+    fn from(err: std::io::Error) -> Self {
+        WriteError::Io(err.to_string())
+    }
+}
+
+impl From<csv::Error> for WriteError {
+    fn from(err: csv::Error) -> Self {
+        WriteError::Csv(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for WriteError {
+    fn from(err: serde_json::Error) -> Self {
+        WriteError::Json(err.to_string())
+    }
+}
+
+/// Normalizes a `provider`/`entry_id` field for Postgres `COPY`: missing
+/// or empty values are rendered as the literal `\N` NULL marker rather
+/// than an empty field.
+fn pg_copy_field(value: &Option<String>) -> &str {
+    match value.as_deref() {
+        None | Some("") => r"\N",
+        Some(v) => v
+    }
+}
+
+/// Normalizes a `stream_id` field for Postgres `COPY`: missing, empty,
+/// and sentinel "no result" values are all rendered as the literal `\N`
+/// NULL marker rather than an empty field.
+fn pg_copy_stream_id(value: &Option<String>) -> &str {
+    match value.as_deref() {
+        None => r"\N",
+        Some("" | "0" | "NO_DATA" | "NO_MATCH" | "NO_SOUND") => r"\N",
+        Some(v) => v
+    }
+}
+
+fn write_pg_copy(periods: &[ViewingPeriod], sink: &mut impl Write) -> Result<(), WriteError> {
+    for period in periods {
+        writeln!(
+            sink,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.3}\t{}",
+            pg_copy_field(&period.provider),
+            period.status,
+            period.user_id,
+            period.query_time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            period.time_in_file.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            period.duration.num_milliseconds() as f64 / 1000.0,
+            pg_copy_stream_id(&period.stream_id),
+            pg_copy_field(&period.entry_id),
+            period.ber,
+            period.valid
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes `periods` to `sink` in the given `format`. CSV/TSV rows and NDJSON
+/// objects both use the `ViewingPeriod` serde mapping (RFC3339 timestamps,
+/// fractional-second duration, status by name); `PgCopy` instead follows
+/// the `\N`-for-NULL convention a `COPY ... FROM STDIN` load expects.
+pub fn write_periods(periods: &[ViewingPeriod], mut sink: impl Write, format: Format) -> Result<(), WriteError> {
+    match format {
+        Format::Csv | Format::Tsv => {
+            let delimiter = if matches!(format, Format::Csv) { b',' } else { b'\t' };
+            let mut writer = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .from_writer(&mut sink);
+
+            for period in periods {
+                writer.serialize(period)?;
+            }
+            writer.flush()?;
+        }
+        Format::Ndjson => {
+            for period in periods {
+                serde_json::to_writer(&mut sink, period)?;
+                writeln!(sink)?;
+            }
+        }
+        Format::PgCopy => write_pg_copy(periods, &mut sink)?
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::viewingperiod::Status;
+
+    use super::*;
+
+    #[test]
+    fn test_pg_copy_normalizes_sentinel_stream_id_but_not_entry_id_or_provider() {
+        let period = ViewingPeriod {
+            provider: Some("0".to_string()),
+            status: Status::Match,
+            user_id: "1".to_string(),
+            query_time: Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
+            time_in_file: Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
+            duration: Duration::seconds(1),
+            stream_id: Some("0".to_string()),
+            entry_id: Some("0".to_string()),
+            ber: 0.0,
+            valid: true
+        };
+
+        let mut out = Vec::new();
+        write_periods(&[period], &mut out, Format::PgCopy).expect("expected pg-copy write to succeed");
+        let line = String::from_utf8(out).expect("expected valid utf8");
+        let fields: Vec<&str> = line.trim_end().split('\t').collect();
+
+        assert_eq!(fields[0], "0", "provider: \"0\" is a legitimate value, not a sentinel");
+        assert_eq!(fields[6], r"\N", "stream_id: \"0\" is the sentinel for no match");
+        assert_eq!(fields[7], "0", "entry_id: \"0\" is a legitimate value, not a sentinel");
+    }
+
+    fn sample_period() -> ViewingPeriod {
+        ViewingPeriod {
+            provider: None,
+            status: Status::Match,
+            user_id: "1".to_string(),
+            query_time: Utc.with_ymd_and_hms(2023, 1, 12, 13, 50, 0).unwrap(),
+            time_in_file: Utc.with_ymd_and_hms(2023, 1, 12, 13, 50, 0).unwrap(),
+            duration: Duration::milliseconds(1_500),
+            stream_id: None,
+            entry_id: None,
+            ber: 0.0,
+            valid: true
+        }
+    }
+
+    #[test]
+    fn test_write_periods_csv_renders_rfc3339_timestamp_and_fractional_duration() {
+        let mut out = Vec::new();
+        write_periods(&[sample_period()], &mut out, Format::Csv).expect("expected csv write to succeed");
+        let csv = String::from_utf8(out).expect("expected valid utf8");
+
+        assert!(csv.contains("2023-01-12T13:50:00.000Z"), "expected an RFC3339 timestamp, got: {}", csv);
+        assert!(csv.contains("1.5"), "expected a fractional-second duration, got: {}", csv);
+    }
+
+    #[test]
+    fn test_write_periods_tsv_renders_rfc3339_timestamp_and_fractional_duration() {
+        let mut out = Vec::new();
+        write_periods(&[sample_period()], &mut out, Format::Tsv).expect("expected tsv write to succeed");
+        let tsv = String::from_utf8(out).expect("expected valid utf8");
+
+        assert!(tsv.contains("2023-01-12T13:50:00.000Z"), "expected an RFC3339 timestamp, got: {}", tsv);
+        assert!(tsv.contains("1.5"), "expected a fractional-second duration, got: {}", tsv);
+        assert!(tsv.contains('\t'), "expected tab-delimited output, got: {}", tsv);
+    }
+
+    #[test]
+    fn test_write_periods_ndjson_renders_rfc3339_timestamp_and_fractional_duration() {
+        let mut out = Vec::new();
+        write_periods(&[sample_period()], &mut out, Format::Ndjson).expect("expected ndjson write to succeed");
+        let ndjson = String::from_utf8(out).expect("expected valid utf8");
+
+        let value: serde_json::Value = serde_json::from_str(ndjson.trim_end()).expect("expected valid json");
+        assert_eq!(value["query_time"], "2023-01-12T13:50:00.000Z");
+        assert_eq!(value["duration"], 1.5);
+    }
+}